@@ -0,0 +1,436 @@
+//! Low-level transports ("buses") that know how to get a single command or data byte
+//! onto the HD44780's pins, whether that's direct GPIO or an I2C expander backpack.
+//!
+//! [`Lcd`](crate::Lcd) is generic over [`DataBus`], so the same high-level API works
+//! unmodified across wiring styles: swap the bus passed to `Lcd::new` and everything
+//! else (`write_str`, `set_cursor`, `create_char`, ...) keeps working.
+
+use embedded_hal::blocking::{delay::DelayMs, i2c};
+use embedded_hal::digital::v2::OutputPin;
+
+use crate::{Backlight, DisplayControl, Mode};
+
+/// Transport used to send one command or data byte to the HD44780 controller.
+pub trait DataBus<D>
+where
+    D: DelayMs<u8>,
+{
+    type Error;
+
+    /// Whether this bus only exposes a 4-bit-wide data path (`D4`..`D7`, or the
+    /// nibble-at-a-time I2C expander backpacks). The HD44780 power-on reset sequence
+    /// differs for 4-bit and 8-bit interfaces, so [`Lcd::init`](crate::Lcd::init)
+    /// consults this to pick the right dance.
+    const FOUR_BIT_MODE: bool;
+
+    fn write(&mut self, byte: u8, mode: Mode, delay: &mut D) -> Result<(), Self::Error>;
+
+    /// Send a single 4-bit value (in the low nibble of `nibble`) as one EN pulse in
+    /// command mode. Used only for the HD44780 power-on reset handshake
+    /// ([`Lcd::init`](crate::Lcd::init)), which must land exactly one nibble per
+    /// pulse even on buses whose normal [`write`](DataBus::write) sends two nibbles
+    /// per byte, since the controller hasn't yet been told which framing to expect.
+    ///
+    /// The default forwards to `write`, which is only correct for buses that are
+    /// already one-pulse-per-byte (i.e. [`EightBitBus`]); buses with two-nibble
+    /// `write` framing must override this.
+    fn write_nibble(&mut self, nibble: u8, delay: &mut D) -> Result<(), Self::Error> {
+        self.write(nibble << 4, Mode::Cmd, delay)
+    }
+}
+
+/// A [`DataBus`] that can also read the controller's busy flag back, letting
+/// [`Lcd`](crate::Lcd) poll for command completion instead of padding with a fixed
+/// worst-case delay. Only buses with a readable data path can implement this (a
+/// PCF8574 backpack with R/W wired, or an MCP23008 backpack); plain GPIO output buses
+/// can't, so callers fall back to the timed path.
+pub trait ReadableDataBus<D>: DataBus<D>
+where
+    D: DelayMs<u8>,
+{
+    /// Set R/W high and R/S low, pulse EN, and read back the busy flag (the high bit
+    /// of the nibble/byte returned). Returns `true` while the controller is still
+    /// executing the previous command.
+    fn read_busy_flag(&mut self, delay: &mut D) -> Result<bool, Self::Error>;
+}
+
+/// Bus for the common PCF8574-style I2C backpack: RS/RW/EN/Backlight and D4..D7 are
+/// all packed into a single byte written over I2C, one nibble at a time.
+pub struct I2CBus<'a, I> {
+    i2c: &'a mut I,
+    address: u8,
+    backlight_state: Backlight,
+}
+
+impl<'a, I> I2CBus<'a, I>
+where
+    I: i2c::Write,
+{
+    pub fn new(i2c: &'a mut I, address: u8) -> Self {
+        Self {
+            i2c,
+            address,
+            backlight_state: Backlight::On,
+        }
+    }
+
+    pub fn backlight(&mut self, backlight: Backlight) -> Result<(), I::Error> {
+        self.backlight_state = backlight;
+        self.i2c.write(
+            self.address,
+            &[DisplayControl::DisplayOn as u8 | backlight as u8],
+        )
+    }
+
+    fn write4bits(&mut self, data: u8) -> Result<(), I::Error> {
+        self.i2c.write(
+            self.address,
+            &[data | DisplayControl::DisplayOn as u8 | self.backlight_state as u8],
+        )?;
+        self.i2c.write(
+            self.address,
+            &[DisplayControl::Off as u8 | self.backlight_state as u8],
+        )?;
+        Ok(())
+    }
+}
+
+impl<'a, I, D> DataBus<D> for I2CBus<'a, I>
+where
+    I: i2c::Write,
+    D: DelayMs<u8>,
+{
+    type Error = I::Error;
+    const FOUR_BIT_MODE: bool = true;
+
+    fn write(&mut self, byte: u8, mode: Mode, _delay: &mut D) -> Result<(), Self::Error> {
+        let high_bits: u8 = byte & 0xf0;
+        let low_bits: u8 = (byte << 4) & 0xf0;
+        self.write4bits(high_bits | mode as u8)?;
+        self.write4bits(low_bits | mode as u8)?;
+        Ok(())
+    }
+
+    fn write_nibble(&mut self, nibble: u8, _delay: &mut D) -> Result<(), Self::Error> {
+        self.write4bits(((nibble << 4) & 0xf0) | Mode::Cmd as u8)
+    }
+}
+
+impl<'a, I, D> ReadableDataBus<D> for I2CBus<'a, I>
+where
+    I: i2c::Write + i2c::Read<Error = <I as i2c::Write>::Error>,
+    D: DelayMs<u8>,
+{
+    fn read_busy_flag(&mut self, _delay: &mut D) -> Result<bool, Self::Error> {
+        // R/W high, R/S low; D4..D7 held high so the PCF8574's quasi-bidirectional
+        // pins can be driven by the LCD instead of us.
+        let rw = 0x02 | self.backlight_state as u8;
+
+        self.i2c
+            .write(self.address, &[0xf0 | rw | DisplayControl::DisplayOn as u8])?;
+        let mut buf = [0u8; 1];
+        self.i2c.read(self.address, &mut buf)?;
+        let busy = buf[0] & 0x80 != 0;
+        self.i2c.write(self.address, &[0xf0 | rw])?;
+
+        // The controller expects two EN pulses per byte read (busy flag + top 3 bits
+        // of the address counter, then the low nibble); we only need the first.
+        self.i2c
+            .write(self.address, &[0xf0 | rw | DisplayControl::DisplayOn as u8])?;
+        self.i2c.read(self.address, &mut buf)?;
+        self.i2c.write(self.address, &[0xf0 | rw])?;
+
+        Ok(busy)
+    }
+}
+
+/// MCP23008-based I2C backpack. Unlike the PCF8574, the MCP23008 is register
+/// addressed, so every GPIO write is a two-byte I2C transfer (register address, then
+/// data), and the pin directions must be configured as outputs once up front.
+pub struct I2CMCP23008Bus<'a, I> {
+    i2c: &'a mut I,
+    address: u8,
+    backlight_state: Backlight,
+}
+
+const MCP23008_IODIR: u8 = 0x00;
+const MCP23008_GPIO: u8 = 0x09;
+
+impl<'a, I> I2CMCP23008Bus<'a, I>
+where
+    I: i2c::Write,
+{
+    /// Create a new bus, configuring all 8 MCP23008 pins as outputs.
+    pub fn new(i2c: &'a mut I, address: u8) -> Result<Self, I::Error> {
+        i2c.write(address, &[MCP23008_IODIR, 0x00])?;
+        Ok(Self {
+            i2c,
+            address,
+            backlight_state: Backlight::On,
+        })
+    }
+
+    pub fn backlight(&mut self, backlight: Backlight) -> Result<(), I::Error> {
+        self.backlight_state = backlight;
+        self.i2c.write(
+            self.address,
+            &[
+                MCP23008_GPIO,
+                DisplayControl::DisplayOn as u8 | backlight as u8,
+            ],
+        )
+    }
+
+    fn write4bits(&mut self, data: u8) -> Result<(), I::Error> {
+        self.i2c.write(
+            self.address,
+            &[
+                MCP23008_GPIO,
+                data | DisplayControl::DisplayOn as u8 | self.backlight_state as u8,
+            ],
+        )?;
+        self.i2c.write(
+            self.address,
+            &[
+                MCP23008_GPIO,
+                DisplayControl::Off as u8 | self.backlight_state as u8,
+            ],
+        )?;
+        Ok(())
+    }
+}
+
+impl<'a, I, D> DataBus<D> for I2CMCP23008Bus<'a, I>
+where
+    I: i2c::Write,
+    D: DelayMs<u8>,
+{
+    type Error = I::Error;
+    const FOUR_BIT_MODE: bool = true;
+
+    fn write(&mut self, byte: u8, mode: Mode, _delay: &mut D) -> Result<(), Self::Error> {
+        let high_bits: u8 = byte & 0xf0;
+        let low_bits: u8 = (byte << 4) & 0xf0;
+        self.write4bits(high_bits | mode as u8)?;
+        self.write4bits(low_bits | mode as u8)?;
+        Ok(())
+    }
+
+    fn write_nibble(&mut self, nibble: u8, _delay: &mut D) -> Result<(), Self::Error> {
+        self.write4bits(((nibble << 4) & 0xf0) | Mode::Cmd as u8)
+    }
+}
+
+impl<'a, I, D> ReadableDataBus<D> for I2CMCP23008Bus<'a, I>
+where
+    I: i2c::Write + i2c::Read<Error = <I as i2c::Write>::Error>,
+    D: DelayMs<u8>,
+{
+    fn read_busy_flag(&mut self, _delay: &mut D) -> Result<bool, Self::Error> {
+        let rw = 0x02 | self.backlight_state as u8;
+
+        // D4..D7 must be switched to inputs before the LCD can drive them back.
+        self.i2c.write(self.address, &[MCP23008_IODIR, 0xf0])?;
+
+        self.i2c.write(
+            self.address,
+            &[MCP23008_GPIO, rw | DisplayControl::DisplayOn as u8],
+        )?;
+        let mut buf = [0u8; 1];
+        self.i2c.read(self.address, &mut buf)?;
+        let busy = buf[0] & 0x80 != 0;
+        self.i2c.write(self.address, &[MCP23008_GPIO, rw])?;
+
+        // The controller expects two EN pulses per byte read; we only need the first.
+        self.i2c.write(
+            self.address,
+            &[MCP23008_GPIO, rw | DisplayControl::DisplayOn as u8],
+        )?;
+        self.i2c.read(self.address, &mut buf)?;
+        self.i2c.write(self.address, &[MCP23008_GPIO, rw])?;
+
+        // Restore D4..D7 as outputs for subsequent writes.
+        self.i2c.write(self.address, &[MCP23008_IODIR, 0x00])?;
+
+        Ok(busy)
+    }
+}
+
+fn set_pin<P>(pin: &mut P, high: bool) -> Result<(), P::Error>
+where
+    P: OutputPin,
+{
+    if high {
+        pin.set_high()
+    } else {
+        pin.set_low()
+    }
+}
+
+/// Bus for a directly-wired 8-bit parallel interface: RS, EN and all of D0..D7 as
+/// individual [`OutputPin`]s.
+pub struct EightBitBus<RS, EN, D0, D1, D2, D3, D4, D5, D6, D7> {
+    rs: RS,
+    en: EN,
+    d0: D0,
+    d1: D1,
+    d2: D2,
+    d3: D3,
+    d4: D4,
+    d5: D5,
+    d6: D6,
+    d7: D7,
+}
+
+impl<RS, EN, D0, D1, D2, D3, D4, D5, D6, D7, E> EightBitBus<RS, EN, D0, D1, D2, D3, D4, D5, D6, D7>
+where
+    RS: OutputPin<Error = E>,
+    EN: OutputPin<Error = E>,
+    D0: OutputPin<Error = E>,
+    D1: OutputPin<Error = E>,
+    D2: OutputPin<Error = E>,
+    D3: OutputPin<Error = E>,
+    D4: OutputPin<Error = E>,
+    D5: OutputPin<Error = E>,
+    D6: OutputPin<Error = E>,
+    D7: OutputPin<Error = E>,
+{
+    // One argument per physical pin is the clearest API for direct GPIO wiring.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        rs: RS,
+        en: EN,
+        d0: D0,
+        d1: D1,
+        d2: D2,
+        d3: D3,
+        d4: D4,
+        d5: D5,
+        d6: D6,
+        d7: D7,
+    ) -> Self {
+        Self {
+            rs,
+            en,
+            d0,
+            d1,
+            d2,
+            d3,
+            d4,
+            d5,
+            d6,
+            d7,
+        }
+    }
+
+    fn pulse_enable<D: DelayMs<u8>>(&mut self, delay: &mut D) -> Result<(), E> {
+        self.en.set_high()?;
+        delay.delay_ms(1);
+        self.en.set_low()?;
+        Ok(())
+    }
+}
+
+impl<RS, EN, D0, D1, D2, D3, D4, D5, D6, D7, E, D> DataBus<D>
+    for EightBitBus<RS, EN, D0, D1, D2, D3, D4, D5, D6, D7>
+where
+    RS: OutputPin<Error = E>,
+    EN: OutputPin<Error = E>,
+    D0: OutputPin<Error = E>,
+    D1: OutputPin<Error = E>,
+    D2: OutputPin<Error = E>,
+    D3: OutputPin<Error = E>,
+    D4: OutputPin<Error = E>,
+    D5: OutputPin<Error = E>,
+    D6: OutputPin<Error = E>,
+    D7: OutputPin<Error = E>,
+    D: DelayMs<u8>,
+{
+    type Error = E;
+    const FOUR_BIT_MODE: bool = false;
+
+    fn write(&mut self, byte: u8, mode: Mode, delay: &mut D) -> Result<(), Self::Error> {
+        set_pin(&mut self.rs, mode as u8 != 0)?;
+        set_pin(&mut self.d0, byte & 0x01 != 0)?;
+        set_pin(&mut self.d1, byte & 0x02 != 0)?;
+        set_pin(&mut self.d2, byte & 0x04 != 0)?;
+        set_pin(&mut self.d3, byte & 0x08 != 0)?;
+        set_pin(&mut self.d4, byte & 0x10 != 0)?;
+        set_pin(&mut self.d5, byte & 0x20 != 0)?;
+        set_pin(&mut self.d6, byte & 0x40 != 0)?;
+        set_pin(&mut self.d7, byte & 0x80 != 0)?;
+        self.pulse_enable(delay)
+    }
+}
+
+/// Bus for a directly-wired 4-bit parallel interface: RS, EN and D4..D7 as individual
+/// [`OutputPin`]s (D0..D3 left unconnected, as the HD44780 allows).
+pub struct FourBitBus<RS, EN, D4, D5, D6, D7> {
+    rs: RS,
+    en: EN,
+    d4: D4,
+    d5: D5,
+    d6: D6,
+    d7: D7,
+}
+
+impl<RS, EN, D4, D5, D6, D7, E> FourBitBus<RS, EN, D4, D5, D6, D7>
+where
+    RS: OutputPin<Error = E>,
+    EN: OutputPin<Error = E>,
+    D4: OutputPin<Error = E>,
+    D5: OutputPin<Error = E>,
+    D6: OutputPin<Error = E>,
+    D7: OutputPin<Error = E>,
+{
+    pub fn new(rs: RS, en: EN, d4: D4, d5: D5, d6: D6, d7: D7) -> Self {
+        Self {
+            rs,
+            en,
+            d4,
+            d5,
+            d6,
+            d7,
+        }
+    }
+
+    fn pulse_enable<D: DelayMs<u8>>(&mut self, delay: &mut D) -> Result<(), E> {
+        self.en.set_high()?;
+        delay.delay_ms(1);
+        self.en.set_low()?;
+        Ok(())
+    }
+
+    fn pulse_nibble<D: DelayMs<u8>>(&mut self, nibble: u8, delay: &mut D) -> Result<(), E> {
+        set_pin(&mut self.d4, nibble & 0x01 != 0)?;
+        set_pin(&mut self.d5, nibble & 0x02 != 0)?;
+        set_pin(&mut self.d6, nibble & 0x04 != 0)?;
+        set_pin(&mut self.d7, nibble & 0x08 != 0)?;
+        self.pulse_enable(delay)
+    }
+}
+
+impl<RS, EN, D4, D5, D6, D7, E, D> DataBus<D> for FourBitBus<RS, EN, D4, D5, D6, D7>
+where
+    RS: OutputPin<Error = E>,
+    EN: OutputPin<Error = E>,
+    D4: OutputPin<Error = E>,
+    D5: OutputPin<Error = E>,
+    D6: OutputPin<Error = E>,
+    D7: OutputPin<Error = E>,
+    D: DelayMs<u8>,
+{
+    type Error = E;
+    const FOUR_BIT_MODE: bool = true;
+
+    fn write(&mut self, byte: u8, mode: Mode, delay: &mut D) -> Result<(), Self::Error> {
+        set_pin(&mut self.rs, mode as u8 != 0)?;
+        self.pulse_nibble(byte >> 4, delay)?;
+        self.pulse_nibble(byte & 0x0f, delay)
+    }
+
+    fn write_nibble(&mut self, nibble: u8, delay: &mut D) -> Result<(), Self::Error> {
+        set_pin(&mut self.rs, false)?;
+        self.pulse_nibble(nibble, delay)
+    }
+}
@@ -1,7 +1,8 @@
 #![no_std]
 //! Driver to write characters to LCD displays with a LM1602 connected via i2c like [this one] with
-//! 16x2 characters. It requires a I2C instance implementing [`embedded_hal::blocking::i2c::Write`]
-//! and a instance to delay execution with [`embedded_hal::blocking::delay::DelayMs`].
+//! 16x2 characters, or directly wired over 4-bit/8-bit GPIO. It requires a delay instance to delay
+//! execution with [`embedded_hal::blocking::delay::DelayMs`], and a [`DataBus`] matching your wiring
+//! ([`I2CBus`], [`I2CMCP23008Bus`], [`EightBitBus`] or [`FourBitBus`]).
 //!
 //! Usage:
 //! ```
@@ -19,11 +20,11 @@
 //! );
 //! let mut delay = arduino_hal::Delay::new();
 //!
-//! let mut lcd = lcd_lcm1602_i2c::Lcd::new(&mut i2c, &mut delay)
-//!     .address(LCD_ADDRESS)
+//! let bus = lcd_lcm1602_i2c::I2CBus::new(&mut i2c, LCD_ADDRESS);
+//! let mut lcd = lcd_lcm1602_i2c::Lcd::new(bus)
 //!     .cursor_on(false) // no visible cursor
 //!     .rows(2) // two rows
-//!     .init().unwrap();
+//!     .init(&mut delay).unwrap();
 //! ```
 //!
 //! This [site][lcd address] describes how to find the address of your LCD devices.
@@ -31,25 +32,42 @@
 //! [this one]: https://funduinoshop.com/elektronische-module/displays/lcd/16x02-i2c-lcd-modul-hintergrundbeleuchtung-blau
 //! [lcd address]: https://www.ardumotive.com/i2clcden.html
 
+mod bus;
+
+pub use bus::{DataBus, EightBitBus, FourBitBus, I2CBus, I2CMCP23008Bus, ReadableDataBus};
+
 use core::marker::PhantomData;
 
 use embedded_hal::blocking::{delay::DelayMs, i2c};
 
 use ufmt_write::uWrite;
 
-/// API to write to the LCD.
+/// API to write to the LCD. Generic over the [`DataBus`] so the same high-level
+/// methods work whether the display is wired via I2C or direct GPIO.
 /// PhantomData<D> used to ensure correct Delay without having to take ownership of delay.
-pub struct Lcd<'a, I, D>
+pub struct Lcd<B, D>
 where
-    I: i2c::Write,
+    B: DataBus<D>,
     D: DelayMs<u8>,
 {
-    i2c: &'a mut I,
-    address: u8,
+    bus: B,
     rows: u8,
-    backlight_state: Backlight,
+    cols: u8,
     cursor_on: bool,
     cursor_blink: bool,
+    /// Cached Display Control data bits (`DisplayOn`/`CursorOn`/`CursorBlink`), without
+    /// the `Mode::DisplayControl` opcode bit, so a single toggle can flip one bit and
+    /// resend the whole command.
+    display_control: u8,
+    /// Cached Entry Mode data bits (increment/shift), without the `EntryModeSet` opcode bit.
+    entry_mode: u8,
+    /// Last DDRAM address knowingly set via [`Lcd::set_cursor`], [`Lcd::create_char`] or
+    /// [`Lcd::return_home_polled`], used to restore cursor position after busy-flag polling (see [`ReadableDataBus`]),
+    /// which can disturb it. Not updated by [`Lcd::write_str`]: the driver doesn't track
+    /// the DDRAM pointer's row-wrap/autoscroll/direction-dependent advance through
+    /// regular character writes, so [`Lcd::wait_ready`] is unsafe to interleave with
+    /// `write_str` and should only be called right after `set_cursor` or `create_char`.
+    cursor_addr: u8,
     phantomdata: PhantomData<D>,
 }
 
@@ -66,9 +84,17 @@ pub enum Backlight {
     On = 0x08,
 }
 
+/// Direction used by [`Lcd::set_entry_direction`], [`Lcd::shift_display`] and
+/// [`Lcd::shift_cursor`].
+#[derive(Copy, Clone)]
+pub enum Direction {
+    Left,
+    Right,
+}
+
 #[repr(u8)]
 #[derive(Copy, Clone)]
-enum Mode {
+pub enum Mode {
     Cmd = 0x00,
     Data = 0x01,
     DisplayControl = 0x08,
@@ -78,40 +104,55 @@ enum Mode {
 enum Commands {
     Clear = 0x01,
     ReturnHome = 0x02,
-    ShiftCursor = 0x14,
+    EntryModeSet = 0x04,
+    CursorOrDisplayShift = 0x10,
+    SetCGRAMAddr = 0x40,
+    SetDDRAMAddr = 0x80,
+}
+
+/// Entry Mode data bits, combined with [`Commands::EntryModeSet`].
+enum EntryMode {
+    Shift = 0x01,
+    Increment = 0x02,
+}
+
+/// Cursor/Display Shift data bits, combined with [`Commands::CursorOrDisplayShift`].
+enum Shift {
+    Right = 0x04,
+    Display = 0x08,
 }
 
+/// DDRAM address of column 0 for each row, indexed by row number.
+///
+/// Rows 2 and 3 (on 20x4 panels) continue lines 0 and 1 respectively rather than
+/// starting a new block of DDRAM, which is why they aren't simply `2 * 40` and `3 * 40`.
+const ROW_OFFSETS: [u8; 4] = [0x00, 0x40, 0x14, 0x54];
+
 enum BitMode {
     Bit4 = 0x00,
     Bit8 = 0x10,
 }
 
-impl<'a, I, D> Lcd<'a, I, D>
+impl<B, D> Lcd<B, D>
 where
-    I: i2c::Write,
+    B: DataBus<D>,
     D: DelayMs<u8>,
 {
-    /// Create new instance with only the I2C and delay instance.
-    pub fn new(i2c: &'a mut I, backlight_state: Backlight) -> Self {
+    /// Create new instance from a bus matching your wiring, e.g. [`I2CBus`].
+    pub fn new(bus: B) -> Self {
         Self {
-            i2c,
-            backlight_state,
-            address: 0,
+            bus,
             rows: 0,
+            cols: 0,
             cursor_blink: false,
             cursor_on: false,
+            display_control: DisplayControl::DisplayOn as u8,
+            entry_mode: EntryMode::Increment as u8,
+            cursor_addr: 0x00,
             phantomdata: PhantomData,
         }
     }
 
-    /// Set I2C address, see [lcd address].
-    ///
-    /// [lcd address]: https://badboi.dev/rust,/microcontrollers/2020/11/09/i2c-hello-world.html
-    pub fn address(mut self, address: u8) -> Self {
-        self.address = address;
-        self
-    }
-
     pub fn cursor_on(mut self, on: bool) -> Self {
         self.cursor_on = on;
         self
@@ -128,6 +169,12 @@ where
         self
     }
 
+    /// Number of columns, used to validate [`Lcd::set_cursor`] against the panel geometry.
+    pub fn cols(mut self, cols: u8) -> Self {
+        self.cols = cols;
+        self
+    }
+
     /// Initializes the hardware.
     ///
     /// Actual procedure is a bit obscure. This one was compiled from this [blog post],
@@ -136,105 +183,541 @@ where
     /// [datasheet]: https://www.openhacks.com/uploadsproductos/eone-1602a1.pdf
     /// [code]: https://github.com/jalhadi/i2c-hello-world/blob/main/src/main.rs
     /// [blog post]: https://badboi.dev/rust,/microcontrollers/2020/11/09/i2c-hello-world.html
-    pub fn init(mut self, delay: &mut D) -> Result<Self, <I as i2c::Write>::Error> {
+    pub fn init(mut self, delay: &mut D) -> Result<Self, B::Error> {
         // Initial delay to wait for init after power on.
         delay.delay_ms(80);
 
-        // Init with 8 bit mode
-        let mode_8bit = Mode::FunctionSet as u8 | BitMode::Bit8 as u8;
-        self.write4bits(mode_8bit)?;
-        delay.delay_ms(5);
-        self.write4bits(mode_8bit)?;
-        delay.delay_ms(5);
-        self.write4bits(mode_8bit)?;
-        delay.delay_ms(5);
+        // 4-bit interfaces (direct GPIO or nibble-at-a-time I2C expanders) must be
+        // resynced into a known state before use, regardless of their actual power-on
+        // state; true 8-bit interfaces need no such dance.
+        if B::FOUR_BIT_MODE {
+            // Init with 8 bit mode. The controller is still in its indeterminate
+            // power-on state here, so these must land as a single high-nibble EN
+            // pulse each, not the normal two-nibble `write`.
+            let mode_8bit = Mode::FunctionSet as u8 | BitMode::Bit8 as u8;
+            self.bus.write_nibble(mode_8bit >> 4, delay)?;
+            delay.delay_ms(5);
+            self.bus.write_nibble(mode_8bit >> 4, delay)?;
+            delay.delay_ms(5);
+            self.bus.write_nibble(mode_8bit >> 4, delay)?;
+            delay.delay_ms(5);
 
-        // Switch to 4 bit mode
-        let mode_4bit = Mode::FunctionSet as u8 | BitMode::Bit4 as u8;
-        self.write4bits(mode_4bit)?;
+            // Switch to 4 bit mode
+            let mode_4bit = Mode::FunctionSet as u8 | BitMode::Bit4 as u8;
+            self.bus.write_nibble(mode_4bit >> 4, delay)?;
+        }
 
         // Display setup
-        // Set mode either 2 or four lines
-        // TODO: Verify for 20x4 screen
+        // Set mode either 2 or four lines, OR in the data-length bit for buses that
+        // are physically 8 bits wide so the controller matches what's being driven.
         let lines = if self.rows == 0 { 0x00 } else { 0x08 };
-        self.send(Mode::FunctionSet as u8 | lines, Mode::Cmd)?;
+        let bit_mode = if B::FOUR_BIT_MODE {
+            BitMode::Bit4
+        } else {
+            BitMode::Bit8
+        };
+        self.send(
+            Mode::FunctionSet as u8 | bit_mode as u8 | lines,
+            Mode::Cmd,
+            delay,
+        )?;
 
         // Set display on, optionally turn on cursor and cursor blink
-        let mut display_ctrl = DisplayControl::DisplayOn as u8;
+        self.display_control = DisplayControl::DisplayOn as u8;
         if self.cursor_on {
-            display_ctrl |= DisplayControl::CursorOn as u8;
+            self.display_control |= DisplayControl::CursorOn as u8;
 
             if self.cursor_blink {
-                display_ctrl |= DisplayControl::CursorBlink as u8;
+                self.display_control |= DisplayControl::CursorBlink as u8;
             }
         }
-        self.send(Mode::DisplayControl as u8 | display_ctrl, Mode::Cmd)?;
+        self.send_display_control(delay)?;
 
         // Clear Display, also moves cursor to top left
-        self.send(Mode::Cmd as u8 | Commands::Clear as u8, Mode::Cmd)?;
+        self.send(Mode::Cmd as u8 | Commands::Clear as u8, Mode::Cmd, delay)?;
 
-        // Entry right: shifting cursor moves to right
-        self.send(0x04, Mode::Cmd)?;
-        self.backlight(self.backlight_state)?;
+        self.send_entry_mode(delay)?;
         Ok(self)
     }
 
-    fn write4bits(&mut self, data: u8) -> Result<(), <I as i2c::Write>::Error> {
-        self.i2c.write(
-            self.address,
-            &[data | DisplayControl::DisplayOn as u8 | self.backlight_state as u8],
-        )?;
-        self.i2c.write(
-            self.address,
-            &[DisplayControl::Off as u8 | self.backlight_state as u8],
-        )?;
-        Ok(())
+    fn send(&mut self, data: u8, mode: Mode, delay: &mut D) -> Result<(), B::Error> {
+        self.bus.write(data, mode, delay)
     }
 
-    fn send(&mut self, data: u8, mode: Mode) -> Result<(), <I as i2c::Write>::Error> {
-        let high_bits: u8 = data & 0xf0;
-        let low_bits: u8 = (data << 4) & 0xf0;
-        self.write4bits(high_bits | mode as u8)?;
-        self.write4bits(low_bits | mode as u8)?;
-        Ok(())
+    fn send_display_control(&mut self, delay: &mut D) -> Result<(), B::Error> {
+        self.send(
+            Mode::DisplayControl as u8 | self.display_control,
+            Mode::Cmd,
+            delay,
+        )
+    }
+
+    fn send_entry_mode(&mut self, delay: &mut D) -> Result<(), B::Error> {
+        self.send(
+            Commands::EntryModeSet as u8 | self.entry_mode,
+            Mode::Cmd,
+            delay,
+        )
+    }
+
+    fn set_display_control_bit(&mut self, bit: u8, on: bool) {
+        if on {
+            self.display_control |= bit;
+        } else {
+            self.display_control &= !bit;
+        }
+    }
+
+    fn set_entry_mode_bit(&mut self, bit: u8, on: bool) {
+        if on {
+            self.entry_mode |= bit;
+        } else {
+            self.entry_mode &= !bit;
+        }
+    }
+
+    /// Turn the whole display on or off, e.g. to save power. The cursor position and
+    /// DDRAM/CGRAM contents are preserved while off.
+    pub fn set_display(&mut self, on: bool, delay: &mut D) -> Result<(), B::Error> {
+        self.set_display_control_bit(DisplayControl::DisplayOn as u8, on);
+        self.send_display_control(delay)
+    }
+
+    /// Show or hide the underline cursor.
+    pub fn set_cursor_visibility(&mut self, visible: bool, delay: &mut D) -> Result<(), B::Error> {
+        self.cursor_on = visible;
+        self.set_display_control_bit(DisplayControl::CursorOn as u8, visible);
+        self.send_display_control(delay)
+    }
+
+    /// Turn the blinking cursor block on or off.
+    pub fn set_cursor_blink(&mut self, on: bool, delay: &mut D) -> Result<(), B::Error> {
+        self.cursor_blink = on;
+        self.set_display_control_bit(DisplayControl::CursorBlink as u8, on);
+        self.send_display_control(delay)
+    }
+
+    /// Enable or disable autoscroll: when on, writing a character shifts the whole
+    /// display instead of just moving the cursor.
+    pub fn set_autoscroll(&mut self, on: bool, delay: &mut D) -> Result<(), B::Error> {
+        self.set_entry_mode_bit(EntryMode::Shift as u8, on);
+        self.send_entry_mode(delay)
+    }
+
+    /// Set which direction the cursor moves after writing a character.
+    pub fn set_entry_direction(
+        &mut self,
+        direction: Direction,
+        delay: &mut D,
+    ) -> Result<(), B::Error> {
+        self.set_entry_mode_bit(
+            EntryMode::Increment as u8,
+            matches!(direction, Direction::Right),
+        );
+        self.send_entry_mode(delay)
     }
 
-    pub fn backlight(&mut self, backlight: Backlight) -> Result<(), <I as i2c::Write>::Error> {
-        self.backlight_state = backlight;
-        self.i2c.write(
-            self.address,
-            &[DisplayControl::DisplayOn as u8 | backlight as u8],
+    /// Shift the entire display contents left or right without changing DDRAM contents.
+    pub fn shift_display(&mut self, direction: Direction, delay: &mut D) -> Result<(), B::Error> {
+        let mut bits = Shift::Display as u8;
+        if matches!(direction, Direction::Right) {
+            bits |= Shift::Right as u8;
+        }
+        self.send(
+            Commands::CursorOrDisplayShift as u8 | bits,
+            Mode::Cmd,
+            delay,
+        )
+    }
+
+    /// Move the cursor left or right without writing a character.
+    pub fn shift_cursor(&mut self, direction: Direction, delay: &mut D) -> Result<(), B::Error> {
+        let mut bits = 0;
+        if matches!(direction, Direction::Right) {
+            bits |= Shift::Right as u8;
+        }
+        self.send(
+            Commands::CursorOrDisplayShift as u8 | bits,
+            Mode::Cmd,
+            delay,
         )
     }
 
     /// Write string to display.
-    pub fn write_str(&mut self, data: &str) -> Result<(), <I as i2c::Write>::Error> {
+    pub fn write_str(&mut self, data: &str, delay: &mut D) -> Result<(), B::Error> {
         for c in data.chars() {
-            self.send(c as u8, Mode::Data)?;
+            self.send(c as u8, Mode::Data, delay)?;
         }
         Ok(())
     }
 
     /// Clear the display
-    pub fn clear(&mut self) -> Result<(), <I as i2c::Write>::Error> {
-        self.send(Commands::Clear as u8, Mode::Cmd)?;
+    pub fn clear(&mut self, delay: &mut D) -> Result<(), B::Error> {
+        self.send(Commands::Clear as u8, Mode::Cmd, delay)?;
         Ok(())
     }
 
     /// Return cursor to upper left corner, i.e. (0,0).
-    pub fn return_home(&mut self, delay: &mut D) -> Result<(), <I as i2c::Write>::Error> {
-        self.send(Commands::ReturnHome as u8, Mode::Cmd)?;
+    pub fn return_home(&mut self, delay: &mut D) -> Result<(), B::Error> {
+        self.send(Commands::ReturnHome as u8, Mode::Cmd, delay)?;
         delay.delay_ms(10);
         Ok(())
     }
 
-    /// Set the cursor to (rows, col). Coordinates are zero-based.
-    pub fn set_cursor(&mut self, row: u8, col: u8, delay: &mut D) -> Result<(), <I as i2c::Write>::Error> {
-        self.return_home(delay)?;
-        let shift: u8 = row * 40 + col;
-        for _i in 0..shift {
-            self.send(Commands::ShiftCursor as u8, Mode::Cmd)?;
+    /// Set the cursor to (row, col). Coordinates are zero-based.
+    pub fn set_cursor(&mut self, row: u8, col: u8, delay: &mut D) -> Result<(), B::Error> {
+        debug_assert!(
+            (row as usize) < ROW_OFFSETS.len(),
+            "row out of range for HD44780 DDRAM layout"
+        );
+        debug_assert!(
+            self.rows == 0 || row < self.rows,
+            "row out of range for configured geometry"
+        );
+        debug_assert!(
+            self.cols == 0 || col < self.cols,
+            "col out of range for configured geometry"
+        );
+
+        let addr = ROW_OFFSETS[row as usize] + col;
+        self.cursor_addr = addr;
+        self.send(Commands::SetDDRAMAddr as u8 | addr, Mode::Cmd, delay)
+    }
+
+    /// Load a custom 5x8 glyph into CGRAM at `location` (0-7), one bitmap row per `u8`,
+    /// top row first. Only the low 5 bits of each byte are visible pixel columns.
+    ///
+    /// Once loaded, the glyph is printed like any other character by writing byte
+    /// `location` (0-7), e.g. via [`Lcd::write_str`].
+    pub fn create_char(
+        &mut self,
+        location: u8,
+        charmap: [u8; 8],
+        delay: &mut D,
+    ) -> Result<(), B::Error> {
+        debug_assert!(location < 8, "HD44780 only has 8 CGRAM slots");
+
+        self.send(
+            Commands::SetCGRAMAddr as u8 | ((location & 0x7) << 3),
+            Mode::Cmd,
+            delay,
+        )?;
+        for row in charmap {
+            self.send(row, Mode::Data, delay)?;
+        }
+
+        // Writing CGRAM left the controller's address pointer in CGRAM space;
+        // restore DDRAM addressing so the next write_str lands on screen. This also
+        // moves the DDRAM pointer to 0, so track that in cursor_addr too, or
+        // wait_ready's restore would send the stale pre-create_char address instead.
+        self.cursor_addr = 0;
+        self.send(Commands::SetDDRAMAddr as u8, Mode::Cmd, delay)
+    }
+}
+
+impl<B, D> Lcd<B, D>
+where
+    B: ReadableDataBus<D>,
+    D: DelayMs<u8>,
+{
+    /// Poll the busy flag until it clears, instead of padding with a fixed worst-case
+    /// delay. Reading the busy flag can advance the controller's DDRAM address counter
+    /// on some backpacks, so this re-issues the last [`Lcd::set_cursor`],
+    /// [`Lcd::create_char`] or [`Lcd::return_home_polled`] address afterward to restore
+    /// the cursor position. Calling
+    /// this after a run of [`Lcd::write_str`] instead restores a stale address, since
+    /// the driver doesn't track the pointer's advance through regular character
+    /// writes; only call it right after `set_cursor` or `create_char`.
+    pub fn wait_ready(&mut self, delay: &mut D) -> Result<(), B::Error> {
+        while self.bus.read_busy_flag(delay)? {}
+        self.bus.write(
+            Commands::SetDDRAMAddr as u8 | self.cursor_addr,
+            Mode::Cmd,
+            delay,
+        )
+    }
+
+    /// Busy-flag-polling variant of [`Lcd::return_home`], which otherwise pads with a
+    /// fixed 10ms worst-case delay.
+    pub fn return_home_polled(&mut self, delay: &mut D) -> Result<(), B::Error> {
+        self.send(Commands::ReturnHome as u8, Mode::Cmd, delay)?;
+        // Return Home moves the DDRAM pointer to 0; track that so wait_ready's
+        // restore doesn't send the stale pre-return_home address instead.
+        self.cursor_addr = 0;
+        self.wait_ready(delay)
+    }
+}
+
+impl<'a, I, D> Lcd<bus::I2CBus<'a, I>, D>
+where
+    I: i2c::Write,
+    D: DelayMs<u8>,
+{
+    /// Set the I2C backpack's backlight state.
+    pub fn backlight(&mut self, backlight: Backlight) -> Result<(), I::Error> {
+        self.bus.backlight(backlight)
+    }
+}
+
+impl<'a, I, D> Lcd<bus::I2CMCP23008Bus<'a, I>, D>
+where
+    I: i2c::Write,
+    D: DelayMs<u8>,
+{
+    /// Set the I2C backpack's backlight state.
+    pub fn backlight(&mut self, backlight: Backlight) -> Result<(), I::Error> {
+        self.bus.backlight(backlight)
+    }
+}
+
+// `core::fmt::Write` and `uWrite` give us no way to thread the caller's `&mut D`
+// through `write_str(&mut self, s: &str)`, and `Lcd` deliberately doesn't own a
+// `D` (see the `phantomdata` field above) so the same instance can be reused
+// across calls without the borrow-checker fights that come with storing it.
+// These impls bridge that gap with a `D: Default` bound and a throwaway,
+// freshly-`default()`-constructed delay for the timing calls each byte needs.
+//
+// IMPORTANT: this only works correctly for `I2CBus`/`I2CMCP23008Bus`, whose
+// `DataBus::write` ignores the delay argument entirely. For `EightBitBus` and
+// `FourBitBus`, whose `pulse_enable` actually calls `delay.delay_ms(1)` on the
+// passed-in instance, a `D::default()` built fresh on every character may be
+// uncalibrated (e.g. a zero-initialized software delay loop) and produce an
+// EN pulse shorter than the HD44780 requires. It also excludes delay types
+// with no meaningful `Default`, such as `arduino_hal::Delay`. Prefer calling
+// `Lcd::write_str` directly with your real, calibrated delay on GPIO buses;
+// use `write!`/`uwrite!` only where `D::default()` is known to produce a
+// working delay (as it trivially does for the I2C buses, which don't delay).
+
+impl<B, D> core::fmt::Write for Lcd<B, D>
+where
+    B: DataBus<D>,
+    D: DelayMs<u8> + Default,
+{
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let mut delay = D::default();
+        Lcd::write_str(self, s, &mut delay).map_err(|_| core::fmt::Error)
+    }
+}
+
+impl<B, D> uWrite for Lcd<B, D>
+where
+    B: DataBus<D>,
+    D: DelayMs<u8> + Default,
+{
+    type Error = B::Error;
+
+    fn write_str(&mut self, s: &str) -> Result<(), Self::Error> {
+        let mut delay = D::default();
+        Lcd::write_str(self, s, &mut delay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use super::*;
+
+    /// No-op delay for tests: nothing under test depends on real timing.
+    struct NoopDelay;
+
+    impl DelayMs<u8> for NoopDelay {
+        fn delay_ms(&mut self, _ms: u8) {}
+    }
+
+    /// One low-level operation as seen by a [`DataBus`] implementor, recorded so
+    /// tests can assert the exact byte/nibble stream `Lcd` produces.
+    #[derive(Debug, PartialEq)]
+    enum BusOp {
+        Nibble(u8),
+        Byte(u8, u8),
+    }
+
+    /// A four-bit-mode [`DataBus`] that records every operation instead of talking
+    /// to real hardware.
+    struct RecordingBus {
+        ops: Vec<BusOp>,
+    }
+
+    impl RecordingBus {
+        fn new() -> Self {
+            Self { ops: Vec::new() }
         }
-        Ok(())
     }
-}
\ No newline at end of file
+
+    impl DataBus<NoopDelay> for RecordingBus {
+        type Error = ();
+        const FOUR_BIT_MODE: bool = true;
+
+        fn write(&mut self, byte: u8, mode: Mode, _delay: &mut NoopDelay) -> Result<(), ()> {
+            self.ops.push(BusOp::Byte(byte, mode as u8));
+            Ok(())
+        }
+
+        fn write_nibble(&mut self, nibble: u8, _delay: &mut NoopDelay) -> Result<(), ()> {
+            self.ops.push(BusOp::Nibble(nibble));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn set_cursor_uses_ddram_offsets_per_row() {
+        let mut lcd = Lcd::new(RecordingBus::new()).rows(4).cols(20);
+        let mut delay = NoopDelay;
+
+        let cases = [(0u8, 0u8, 0x00u8), (1, 5, 0x45), (2, 0, 0x14), (3, 3, 0x57)];
+        for &(row, col, addr) in &cases {
+            lcd.set_cursor(row, col, &mut delay).unwrap();
+            assert_eq!(lcd.cursor_addr, addr);
+        }
+
+        let expected: Vec<BusOp> = cases
+            .iter()
+            .map(|&(_, _, addr)| BusOp::Byte(Commands::SetDDRAMAddr as u8 | addr, Mode::Cmd as u8))
+            .collect();
+        assert_eq!(lcd.bus.ops, expected);
+    }
+
+    #[test]
+    fn create_char_loads_cgram_and_restores_ddram_addressing() {
+        let mut lcd = Lcd::new(RecordingBus::new());
+        let mut delay = NoopDelay;
+        let charmap = [0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x00];
+
+        lcd.create_char(3, charmap, &mut delay).unwrap();
+
+        let mut expected = std::vec![BusOp::Byte(
+            Commands::SetCGRAMAddr as u8 | (3 << 3),
+            Mode::Cmd as u8,
+        )];
+        expected.extend(
+            charmap
+                .iter()
+                .map(|&row| BusOp::Byte(row, Mode::Data as u8)),
+        );
+        expected.push(BusOp::Byte(Commands::SetDDRAMAddr as u8, Mode::Cmd as u8));
+
+        assert_eq!(lcd.bus.ops, expected);
+        // The DDRAM-restore command above points at address 0; cursor_addr must
+        // track that so a later busy-flag poll doesn't restore a stale address.
+        assert_eq!(lcd.cursor_addr, 0);
+    }
+
+    /// An eight-bit-mode [`DataBus`] that records every operation; used to check
+    /// [`Lcd::init`] picks the 8-bit data-length bit for buses that aren't
+    /// [`DataBus::FOUR_BIT_MODE`].
+    struct RecordingBus8Bit {
+        ops: Vec<BusOp>,
+    }
+
+    impl RecordingBus8Bit {
+        fn new() -> Self {
+            Self { ops: Vec::new() }
+        }
+    }
+
+    impl DataBus<NoopDelay> for RecordingBus8Bit {
+        type Error = ();
+        const FOUR_BIT_MODE: bool = false;
+
+        fn write(&mut self, byte: u8, mode: Mode, _delay: &mut NoopDelay) -> Result<(), ()> {
+            self.ops.push(BusOp::Byte(byte, mode as u8));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn init_four_bit_bus_sends_single_nibble_reset_pulses() {
+        let mut delay = NoopDelay;
+        let lcd = Lcd::new(RecordingBus::new()).init(&mut delay).unwrap();
+
+        let expected = std::vec![
+            // Power-on reset handshake: three 0x3 pulses then switch to 4-bit (0x2),
+            // each a single EN pulse, not `write`'s usual two-nibbles-per-byte framing.
+            BusOp::Nibble(0x3),
+            BusOp::Nibble(0x3),
+            BusOp::Nibble(0x3),
+            BusOp::Nibble(0x2),
+            BusOp::Byte(
+                Mode::FunctionSet as u8 | BitMode::Bit4 as u8,
+                Mode::Cmd as u8
+            ),
+            BusOp::Byte(
+                Mode::DisplayControl as u8 | DisplayControl::DisplayOn as u8,
+                Mode::Cmd as u8,
+            ),
+            BusOp::Byte(Commands::Clear as u8, Mode::Cmd as u8),
+            BusOp::Byte(
+                Commands::EntryModeSet as u8 | EntryMode::Increment as u8,
+                Mode::Cmd as u8,
+            ),
+        ];
+        assert_eq!(lcd.bus.ops, expected);
+    }
+
+    #[test]
+    fn init_eight_bit_bus_sets_data_length_bit_on_function_set() {
+        let mut delay = NoopDelay;
+        let lcd = Lcd::new(RecordingBus8Bit::new()).init(&mut delay).unwrap();
+
+        // No reset-nibble dance for a true 8-bit bus; the very first command must
+        // carry the DL bit so the controller expects 8-bit transfers, matching how
+        // `RecordingBus8Bit::write` is actually driven.
+        assert_eq!(
+            lcd.bus.ops[0],
+            BusOp::Byte(
+                Mode::FunctionSet as u8 | BitMode::Bit8 as u8,
+                Mode::Cmd as u8
+            )
+        );
+    }
+
+    #[test]
+    fn display_and_entry_mode_toggles_rebuild_full_command() {
+        let mut lcd = Lcd::new(RecordingBus::new());
+        let mut delay = NoopDelay;
+
+        lcd.set_cursor_visibility(true, &mut delay).unwrap();
+        lcd.set_cursor_blink(true, &mut delay).unwrap();
+        lcd.set_display(false, &mut delay).unwrap();
+        lcd.set_autoscroll(true, &mut delay).unwrap();
+        lcd.set_entry_direction(Direction::Left, &mut delay)
+            .unwrap();
+
+        let expected = std::vec![
+            BusOp::Byte(
+                Mode::DisplayControl as u8
+                    | DisplayControl::DisplayOn as u8
+                    | DisplayControl::CursorOn as u8,
+                Mode::Cmd as u8,
+            ),
+            BusOp::Byte(
+                Mode::DisplayControl as u8
+                    | DisplayControl::DisplayOn as u8
+                    | DisplayControl::CursorOn as u8
+                    | DisplayControl::CursorBlink as u8,
+                Mode::Cmd as u8,
+            ),
+            // Display off, but cursor/blink bits already set are preserved.
+            BusOp::Byte(
+                Mode::DisplayControl as u8
+                    | DisplayControl::CursorOn as u8
+                    | DisplayControl::CursorBlink as u8,
+                Mode::Cmd as u8,
+            ),
+            // Autoscroll on keeps the default increment bit and adds shift.
+            BusOp::Byte(
+                Commands::EntryModeSet as u8 | EntryMode::Increment as u8 | EntryMode::Shift as u8,
+                Mode::Cmd as u8,
+            ),
+            // Direction::Left clears the increment bit, leaving shift set.
+            BusOp::Byte(
+                Commands::EntryModeSet as u8 | EntryMode::Shift as u8,
+                Mode::Cmd as u8,
+            ),
+        ];
+        assert_eq!(lcd.bus.ops, expected);
+    }
+}